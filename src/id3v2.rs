@@ -0,0 +1,324 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::ReadError;
+
+/// The 10-byte header that precedes the frames of an ID3v2 tag.
+/// Based on: https://id3.org/id3v2.4.0-structure
+struct Header {
+    major_version: u8,
+    size: u32,
+}
+
+impl Header {
+    const SIZE: usize = 10;
+
+    fn read<T: Read>(source: &mut T) -> Result<Self, ReadError> {
+        let mut buf = [0u8; Self::SIZE];
+        source.read_exact(&mut buf)?;
+
+        if &buf[0..3] != b"ID3" {
+            return Err(ReadError::ID3);
+        }
+
+        Ok(Header {
+            major_version: buf[3],
+            size: decode_syncsafe(&buf[6..10]),
+        })
+    }
+}
+
+/// Decodes a 4-byte syncsafe integer, where each byte only uses its lower 7 bits.
+fn decode_syncsafe(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |acc, &b| (acc << 7) | (b & 0x7F) as u32)
+}
+
+/// Decodes the text encoded in an ID3v2 text frame body, given the
+/// encoding byte that precedes it (0 = Latin-1, 1 = UTF-16 with BOM,
+/// 2 = UTF-16BE without BOM, 3 = UTF-8).
+fn decode_text(encoding: u8, bytes: &[u8]) -> Option<String> {
+    let text = match encoding {
+        0 => bytes.iter().map(|&b| b as char).collect(),
+        1 => decode_utf16(bytes, true),
+        2 => decode_utf16(bytes, false),
+        3 => String::from_utf8_lossy(bytes).into_owned(),
+        _ => return None,
+    };
+
+    Some(text.trim_end_matches('\0').to_string())
+}
+
+/// Decodes UTF-16 text, using the first two bytes as a byte-order mark
+/// when `has_bom` is set, and big-endian byte order otherwise.
+fn decode_utf16(bytes: &[u8], has_bom: bool) -> String {
+    let mut bytes = bytes;
+    let mut little_endian = false;
+
+    if has_bom && bytes.len() >= 2 {
+        if bytes[0..2] == [0xFF, 0xFE] {
+            little_endian = true;
+            bytes = &bytes[2..];
+        } else if bytes[0..2] == [0xFE, 0xFF] {
+            bytes = &bytes[2..];
+        }
+    }
+
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            if little_endian {
+                u16::from_le_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_be_bytes([pair[0], pair[1]])
+            }
+        })
+        .collect();
+
+    String::from_utf16_lossy(&units)
+}
+
+/// Finds where the text of a `COMM` frame starts, skipping its 3-byte
+/// language code and the null-terminated short content description.
+fn comm_text_start(encoding: u8, bytes: &[u8]) -> usize {
+    if bytes.len() < 3 {
+        return bytes.len();
+    }
+    let rest = &bytes[3..];
+
+    let separator_len = match encoding {
+        1 | 2 => 2,
+        _ => 1,
+    };
+
+    let mut i = 0;
+    while i + separator_len <= rest.len() {
+        if rest[i..i + separator_len].iter().all(|&b| b == 0) {
+            return 3 + i + separator_len;
+        }
+        i += separator_len;
+    }
+
+    bytes.len()
+}
+
+/// A single ID3v2 frame as read from a tag: its 3- or 4-character frame
+/// ID and raw, still-encoded body.
+struct Frame<'a> {
+    id: &'a [u8],
+    body: &'a [u8],
+}
+
+/// Reads the frames out of an ID3v2 tag body, stopping at the first
+/// frame whose ID is all zero bytes (padding).
+fn read_frames(body: &[u8], major_version: u8) -> Vec<Frame<'_>> {
+    let id_len = if major_version == 2 { 3 } else { 4 };
+    let mut frames = Vec::new();
+    let mut pos = 0;
+
+    while pos + id_len <= body.len() {
+        let id = &body[pos..pos + id_len];
+        if id.iter().all(|&b| b == 0) {
+            break;
+        }
+        pos += id_len;
+
+        let (frame_size, header_len) = if major_version == 2 {
+            if pos + 3 > body.len() {
+                break;
+            }
+            let size = ((body[pos] as usize) << 16)
+                | ((body[pos + 1] as usize) << 8)
+                | body[pos + 2] as usize;
+            (size, 3)
+        } else {
+            if pos + 6 > body.len() {
+                break;
+            }
+            let size = if major_version == 4 {
+                decode_syncsafe(&body[pos..pos + 4]) as usize
+            } else {
+                u32::from_be_bytes([body[pos], body[pos + 1], body[pos + 2], body[pos + 3]])
+                    as usize
+            };
+            (size, 4 + 2)
+        };
+        pos += header_len;
+
+        if pos + frame_size > body.len() {
+            break;
+        }
+
+        frames.push(Frame {
+            id,
+            body: &body[pos..pos + frame_size],
+        });
+        pos += frame_size;
+    }
+
+    frames
+}
+
+#[derive(Debug, Default)]
+/// Represents the common text frames of an ID3v2 tag, mapped to the same
+/// logical fields as [`crate::ID3v1`].
+pub struct ID3v2 {
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    year: Option<String>,
+    track: Option<String>,
+    comment: Option<String>,
+}
+
+impl ID3v2 {
+    /// Creates an `ID3v2` struct by reading the header and frames from the
+    /// start of `source`.
+    pub fn read<T: Seek + Read>(source: &mut T) -> Result<ID3v2, ReadError> {
+        source.seek(SeekFrom::Start(0))?;
+        let header = Header::read(source)?;
+
+        let mut body = vec![0u8; header.size as usize];
+        source.read_exact(&mut body)?;
+
+        let mut tag = ID3v2::default();
+
+        for frame in read_frames(&body, header.major_version) {
+            if frame.body.is_empty() {
+                continue;
+            }
+            let encoding = frame.body[0];
+
+            let text = if frame.id == b"COMM" || frame.id == b"COM" {
+                let start = comm_text_start(encoding, &frame.body[1..]) + 1;
+                decode_text(encoding, frame.body.get(start..).unwrap_or(&[]))
+            } else {
+                decode_text(encoding, &frame.body[1..])
+            };
+
+            match frame.id {
+                b"TIT2" | b"TT2" => tag.title = text,
+                b"TPE1" | b"TP1" => tag.artist = text,
+                b"TALB" | b"TAL" => tag.album = text,
+                b"TYER" | b"TDRC" | b"TYE" => tag.year = text,
+                b"TRCK" | b"TRK" => tag.track = text,
+                b"COMM" | b"COM" => tag.comment = text,
+                _ => {}
+            }
+        }
+
+        Ok(tag)
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn artist(&self) -> Option<&str> {
+        self.artist.as_deref()
+    }
+
+    pub fn album(&self) -> Option<&str> {
+        self.album.as_deref()
+    }
+
+    pub fn year(&self) -> Option<&str> {
+        self.year.as_deref()
+    }
+
+    pub fn track(&self) -> Option<&str> {
+        self.track.as_deref()
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a `u32` as a 4-byte syncsafe integer; the inverse of
+    /// `decode_syncsafe`, used here to build test fixtures.
+    fn encode_syncsafe(value: u32) -> [u8; 4] {
+        let mut bytes = [0u8; 4];
+        let mut value = value;
+        for byte in bytes.iter_mut().rev() {
+            *byte = (value & 0x7F) as u8;
+            value >>= 7;
+        }
+        bytes
+    }
+
+    #[test]
+    fn syncsafe_round_trip() {
+        for value in [0u32, 1, 127, 128, 16384, 268_435_455] {
+            let encoded = encode_syncsafe(value);
+            assert_eq!(decode_syncsafe(&encoded), value);
+        }
+    }
+
+    fn build_text_frame(id: &[u8; 4], encoding: u8, text: &str) -> Vec<u8> {
+        let mut body = vec![encoding];
+        body.extend(text.bytes());
+
+        let mut frame = Vec::new();
+        frame.extend_from_slice(id);
+        frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]); // flags
+        frame.extend(body);
+        frame
+    }
+
+    fn wrap_tag(frames: &[u8]) -> Vec<u8> {
+        let mut tag = Vec::new();
+        tag.extend_from_slice(b"ID3");
+        tag.extend_from_slice(&[4, 0]); // version 2.4.0
+        tag.push(0); // flags
+        tag.extend_from_slice(&encode_syncsafe(frames.len() as u32));
+        tag.extend_from_slice(frames);
+        tag
+    }
+
+    #[test]
+    fn read_text_frames() {
+        let mut frames = Vec::new();
+        frames.extend(build_text_frame(b"TIT2", 0, "Song Title"));
+        frames.extend(build_text_frame(b"TPE1", 0, "Artist Name"));
+        frames.extend(build_text_frame(b"TRCK", 0, "3"));
+
+        let data = wrap_tag(&frames);
+        let mut cursor = std::io::Cursor::new(data);
+
+        let tag = ID3v2::read(&mut cursor).unwrap();
+        assert_eq!(tag.title(), Some("Song Title"));
+        assert_eq!(tag.artist(), Some("Artist Name"));
+        assert_eq!(tag.track(), Some("3"));
+        assert_eq!(tag.album(), None);
+    }
+
+    #[test]
+    fn read_comment_frame() {
+        let mut body = vec![0u8]; // Latin-1 encoding
+        body.extend(b"eng"); // language
+        body.push(0); // empty description, terminated
+        body.extend(b"a comment");
+
+        let mut frame = Vec::new();
+        frame.extend_from_slice(b"COMM");
+        frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend(body);
+
+        let data = wrap_tag(&frame);
+        let mut cursor = std::io::Cursor::new(data);
+
+        let tag = ID3v2::read(&mut cursor).unwrap();
+        assert_eq!(tag.comment(), Some("a comment"));
+    }
+
+    #[test]
+    fn missing_id3_header_is_an_error() {
+        let mut cursor = std::io::Cursor::new(vec![0u8; 20]);
+        assert!(matches!(ID3v2::read(&mut cursor), Err(ReadError::ID3)));
+    }
+}
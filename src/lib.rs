@@ -6,6 +6,9 @@ use std::io::SeekFrom;
 
 use std::convert::From;
 
+mod id3v2;
+pub use id3v2::ID3v2;
+
 #[derive(Debug)]
 /// Differentiate between IO error and an error in reading the ID3 tags.
 pub enum ReadError {
@@ -52,6 +55,60 @@ impl std::fmt::Display for ISO_8859_1 {
     }
 }
 
+impl ISO_8859_1 {
+    /// The fixed-width ID3v1 fields are NUL-padded; this strips that
+    /// padding so callers get back only the text that was actually set.
+    fn trimmed(&self) -> &str {
+        self.0.trim_end_matches('\0')
+    }
+}
+
+/// Reduces `value` to ASCII bytes suitable for an ID3v1 text field.
+/// Characters with a sensible ASCII fallback (accented letters, smart
+/// quotes, em/en dashes, ...) are substituted; anything else is dropped.
+fn to_latin1_lossy(value: &str) -> Vec<u8> {
+    let mut result = Vec::with_capacity(value.len());
+
+    for c in value.chars() {
+        if c.is_ascii() {
+            result.push(c as u8);
+        } else if let Some(replacement) = transliterate(c) {
+            result.extend_from_slice(replacement.as_bytes());
+        }
+    }
+
+    result
+}
+
+/// Maps a non-ASCII character onto its closest ASCII equivalent, or
+/// `None` if it has no sensible fallback.
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => "a",
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' => "A",
+        'è' | 'é' | 'ê' | 'ë' => "e",
+        'È' | 'É' | 'Ê' | 'Ë' => "E",
+        'ì' | 'í' | 'î' | 'ï' => "i",
+        'Ì' | 'Í' | 'Î' | 'Ï' => "I",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' => "o",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' => "O",
+        'ù' | 'ú' | 'û' | 'ü' => "u",
+        'Ù' | 'Ú' | 'Û' | 'Ü' => "U",
+        'ý' | 'ÿ' => "y",
+        'Ý' => "Y",
+        'ñ' => "n",
+        'Ñ' => "N",
+        'ç' => "c",
+        'Ç' => "C",
+        'ß' => "ss",
+        '\u{2018}' | '\u{2019}' | '\u{201A}' => "'",
+        '\u{201C}' | '\u{201D}' | '\u{201E}' => "\"",
+        '\u{2013}' | '\u{2014}' => "-",
+        '\u{2026}' => "...",
+        _ => return None,
+    })
+}
+
 #[derive(Debug)]
 /// Represents ID3v1 tags
 /// Based on: https://id3.org/ID3v1
@@ -62,6 +119,27 @@ pub struct ID3v1 {
     year: ISO_8859_1,
     comment: ISO_8859_1,
     genre: u8,
+    /// Track number, stored in the last two bytes of the comment field by
+    /// the ID3v1.1 convention. `None` means the tag has no track number and
+    /// the comment field uses the full 30 bytes.
+    track: Option<u8>,
+    config: Config,
+}
+
+/// Settings that control how multi-value fields are collapsed into, or
+/// split back out of, the single text slots a tag format provides.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Separator joining multiple artists stored in a single artist field.
+    pub sep_artist: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            sep_artist: String::from(";"),
+        }
+    }
 }
 
 impl std::fmt::Display for ID3v1 {
@@ -82,7 +160,13 @@ Genre: {}\
             self.year,
             self.comment,
             self.get_genre_str()
-        )
+        )?;
+
+        if let Some(track) = self.track {
+            write!(f, "\nTrack: {}", track)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -95,7 +179,6 @@ impl TryFrom<Vec<u8>> for ID3v1 {
         }
 
         let ISO_8859_1(tag) = ISO_8859_1::from(&value[0..=2]);
-        println!("{}", tag);
         if tag != "TAG" {
             return Err(ReadError::ID3);
         }
@@ -104,9 +187,17 @@ impl TryFrom<Vec<u8>> for ID3v1 {
         let artist = ISO_8859_1::from(&value[33..=62]);
         let album = ISO_8859_1::from(&value[63..=92]);
         let year = ISO_8859_1::from(&value[93..=96]);
-        let comment = ISO_8859_1::from(&value[97..=126]);
         let genre = value[127];
 
+        // ID3v1.1: a zero byte followed by a non-zero byte at the end of
+        // the comment field means the last byte is a track number and the
+        // comment is only 28 bytes long.
+        let (comment, track) = if value[125] == 0 && value[126] != 0 {
+            (ISO_8859_1::from(&value[97..=124]), Some(value[126]))
+        } else {
+            (ISO_8859_1::from(&value[97..=126]), None)
+        };
+
         Ok(ID3v1 {
             title,
             artist,
@@ -114,6 +205,8 @@ impl TryFrom<Vec<u8>> for ID3v1 {
             year,
             comment,
             genre,
+            track,
+            config: Config::default(),
         })
     }
 }
@@ -130,19 +223,28 @@ impl From<ID3v1> for Vec<u8> {
             (tags.artist, 30),
             (tags.album, 30),
             (tags.year, 4),
-            (tags.comment, 30),
         ];
 
         for field in text_fields {
-            println!("{}", field.0 .0);
-            for c in field.0 .0.bytes() {
-                println!("{}", c);
-                result.push(c);
+            let width = field.1;
+            let bytes = to_latin1_lossy(&field.0 .0);
+            for &b in bytes.iter().take(width) {
+                result.push(b);
             }
 
-            for _ in field.0 .0.len()..field.1 {
-                result.push(0)
-            }
+            result.extend(std::iter::repeat_n(0, width - bytes.len().min(width)));
+        }
+
+        let comment_len = if tags.track.is_some() { 28 } else { 30 };
+        let comment_bytes = to_latin1_lossy(&tags.comment.0);
+        for &b in comment_bytes.iter().take(comment_len) {
+            result.push(b);
+        }
+        result.extend(std::iter::repeat_n(0, comment_len - comment_bytes.len().min(comment_len)));
+
+        if let Some(track) = tags.track {
+            result.push(0);
+            result.push(track);
         }
 
         result.push(tags.genre);
@@ -154,33 +256,110 @@ impl From<ID3v1> for Vec<u8> {
 impl ID3v1 {
     /// Creates ID3V1 struct from a readable source
     pub fn read<T: Seek + Read>(source: &mut T) -> Result<ID3v1, ReadError> {
+        ID3v1::read_with_config(source, Config::default())
+    }
+
+    /// Like [`ID3v1::read`], but splits and joins multi-value fields
+    /// according to `config` instead of the default.
+    pub fn read_with_config<T: Seek + Read>(
+        source: &mut T,
+        config: Config,
+    ) -> Result<ID3v1, ReadError> {
         source.seek(SeekFrom::End(-128))?;
 
         let mut buff = vec![0; 128];
         // https://users.rust-lang.org/t/read-until-buffer-is-full-or-eof/90184
         source.read_exact(&mut buff)?;
 
-        println!("{:?}", buff);
+        ID3v1::try_from(buff).map(|tags| tags.with_config(config))
+    }
+
+    /// Sets the [`Config`] used to split and join this tag's multi-value
+    /// fields, such as [`AudioTag::artists`].
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Returns the ID3v1.1 track number, if any.
+    pub fn track(&self) -> Option<u8> {
+        self.track
+    }
+
+    /// Sets the ID3v1.1 track number. `None` drops the track and restores
+    /// the comment field to its full 30 bytes.
+    pub fn set_track(&mut self, track: Option<u8>) {
+        self.track = track;
+    }
+
+    /// Synthesizes a tag from a filename, for files with no embedded tag.
+    /// The stem (filename without extension) is split on `" - "`, and the
+    /// resulting components are mapped to fields by their count:
+    /// `title`; `artist - title`; `artist - album - title`; or
+    /// `artist - album - track - title`. A literal `-` with no surrounding
+    /// spaces is kept inside a component instead of splitting it. Fields
+    /// that have no matching component are left empty.
+    pub fn from_filename(name: &str, config: &Config) -> ID3v1 {
+        let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+        let parts: Vec<&str> = stem.split(" - ").collect();
+
+        let mut title = "";
+        let mut artist = "";
+        let mut album = "";
+        let mut track = None;
+
+        match parts.as_slice() {
+            [t] => title = t,
+            [a, t] => {
+                artist = a;
+                title = t;
+            }
+            [a, al, t] => {
+                artist = a;
+                album = al;
+                title = t;
+            }
+            [a, al, tr, t] => {
+                artist = a;
+                album = al;
+                title = t;
+                track = tr.trim().parse().ok();
+            }
+            _ => {}
+        }
 
-        ID3v1::try_from(buff)
+        ID3v1 {
+            title: ISO_8859_1::from(title),
+            artist: ISO_8859_1::from(artist),
+            album: ISO_8859_1::from(album),
+            year: ISO_8859_1::from(""),
+            comment: ISO_8859_1::from(""),
+            genre: 0,
+            track,
+            config: config.clone(),
+        }
     }
 
-    fn get_contents_without_tag<T: Read + Write + Seek>(from: &mut T) -> Result<Vec<u8>, std::io::Error> {
+    /// Returns the contents of `from` with any trailing ID3v1 block
+    /// stripped off, leaving the rest of the file untouched.
+    pub fn get_contents_without_tag<T: Read + Write + Seek>(
+        from: &mut T,
+    ) -> Result<Vec<u8>, std::io::Error> {
         let end_position = if ID3v1::read(from).is_ok() {
-                println!("Has tag");
                 from.seek(SeekFrom::End(-128)).unwrap()
             } else {
-                println!("Has no tag");
                 from.seek(SeekFrom::End(0)).unwrap()
             };
-        
+
         from.seek(SeekFrom::Start(0))?;
         let mut buff = vec![0; end_position as usize];
         from.read_exact(&mut buff)?;
         Ok(buff)
     }
 
-    fn write<T: Read + Write + Seek>(self, destination: &mut T) -> Result<(), std::io::Error> {
+    /// Writes the tag to `destination`, replacing any ID3v1 trailer already
+    /// present and leaving the rest of the file untouched.
+    pub fn write<T: Read + Write + Seek>(self, destination: &mut T) -> Result<(), std::io::Error> {
         let mut contents = Self::get_contents_without_tag(destination)?;
         contents.append(&mut self.into());
         destination.seek(SeekFrom::Start(0))?;
@@ -222,6 +401,149 @@ impl ID3v1 {
             _ => "Unknown",
         }
     }
+
+    /// Looks up the genre byte for a genre name as printed by
+    /// [`ID3v1::get_genre_str`]. Matching is case-sensitive.
+    fn genre_from_name(name: &str) -> Option<u8> {
+        match name {
+            "Blues" => Some(0),
+            "Classic rock" => Some(1),
+            "Country" => Some(2),
+            "Dance" => Some(3),
+            "Disco" => Some(4),
+            "Funk" => Some(5),
+            "Grunge" => Some(6),
+            "Hip-hop" => Some(7),
+            "Jazz" => Some(8),
+            "Metal" => Some(9),
+            "New age" => Some(10),
+            "Oldies" => Some(11),
+            "Other" => Some(12),
+            "Pop" => Some(13),
+            "Rythm and blues" => Some(14),
+            "Rap" => Some(15),
+            "Reggae" => Some(16),
+            "Rock" => Some(17),
+            "Techno" => Some(18),
+            "Industrial" => Some(19),
+            "Alternative" => Some(20),
+            "Ska" => Some(21),
+            "Death Metal" => Some(22),
+            "Soundtrack" => Some(23),
+            "Euro-techno" => Some(25),
+            "Ambient" => Some(26),
+            "Trip-hop" => Some(27),
+            _ => None,
+        }
+    }
+}
+
+/// Common accessors for reading and mutating a tag without naming its
+/// concrete format. Implemented by [`ID3v1`]; [`ID3v2`] does not implement
+/// it and only offers its own read-only accessors.
+pub trait AudioTag {
+    fn title(&self) -> &str;
+    fn set_title(&mut self, title: &str);
+    fn remove_title(&mut self);
+
+    fn artist(&self) -> &str;
+    fn set_artist(&mut self, artist: &str);
+
+    /// Splits the artist field on the tag's configured separator.
+    fn artists(&self) -> Vec<String>;
+    /// Joins `artists` with the tag's configured separator and stores the result.
+    fn set_artists(&mut self, artists: &[&str]);
+
+    fn album(&self) -> &str;
+    fn set_album(&mut self, album: &str);
+
+    fn year(&self) -> &str;
+    fn set_year(&mut self, year: &str);
+
+    fn comment(&self) -> &str;
+    fn set_comment(&mut self, comment: &str);
+
+    fn genre(&self) -> &str;
+    fn set_genre(&mut self, genre: u8);
+    /// Sets the genre from its display name (as returned by [`AudioTag::genre`]).
+    /// Returns `false` and leaves the genre unchanged if the name is not recognized.
+    fn set_genre_by_name(&mut self, name: &str) -> bool;
+}
+
+impl AudioTag for ID3v1 {
+    fn title(&self) -> &str {
+        self.title.trimmed()
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.title = ISO_8859_1::from(title);
+    }
+
+    fn remove_title(&mut self) {
+        self.title = ISO_8859_1::from("");
+    }
+
+    fn artist(&self) -> &str {
+        self.artist.trimmed()
+    }
+
+    fn set_artist(&mut self, artist: &str) {
+        self.artist = ISO_8859_1::from(artist);
+    }
+
+    fn artists(&self) -> Vec<String> {
+        self.artist()
+            .split(self.config.sep_artist.as_str())
+            .map(String::from)
+            .collect()
+    }
+
+    fn set_artists(&mut self, artists: &[&str]) {
+        let joined = artists.join(&self.config.sep_artist);
+        self.set_artist(&joined);
+    }
+
+    fn album(&self) -> &str {
+        self.album.trimmed()
+    }
+
+    fn set_album(&mut self, album: &str) {
+        self.album = ISO_8859_1::from(album);
+    }
+
+    fn year(&self) -> &str {
+        self.year.trimmed()
+    }
+
+    fn set_year(&mut self, year: &str) {
+        self.year = ISO_8859_1::from(year);
+    }
+
+    fn comment(&self) -> &str {
+        self.comment.trimmed()
+    }
+
+    fn set_comment(&mut self, comment: &str) {
+        self.comment = ISO_8859_1::from(comment);
+    }
+
+    fn genre(&self) -> &str {
+        self.get_genre_str()
+    }
+
+    fn set_genre(&mut self, genre: u8) {
+        self.genre = genre;
+    }
+
+    fn set_genre_by_name(&mut self, name: &str) -> bool {
+        match ID3v1::genre_from_name(name) {
+            Some(genre) => {
+                self.genre = genre;
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -312,6 +634,8 @@ mod tests {
             year: year[0..].into(),
             comment: comment[0..].into(),
             genre: 5,
+            track: None,
+            config: Config::default(),
         };
         println!("{:?}", tags);
         tags.write(&mut test_file).unwrap();
@@ -393,4 +717,162 @@ mod tests {
 
         assert_eq!(contents, vec![1, 2]);
     }
+
+    #[test]
+    fn round_trip_v1_0() {
+        let mut test_file = TestFile::new();
+        let tags = ID3v1 {
+            title: "testsong".into(),
+            artist: "testartist".into(),
+            album: "testalbum".into(),
+            year: "2024".into(),
+            comment: "a comment long enough to use all 30 bytes!!".into(),
+            genre: 5,
+            track: None,
+            config: Config::default(),
+        };
+        tags.write(&mut test_file).unwrap();
+
+        let read_back = ID3v1::read(&mut test_file).unwrap();
+        assert_eq!(read_back.comment.0, "a comment long enough to use a");
+        assert_eq!(read_back.track, None);
+        assert_eq!(read_back.genre, 5);
+    }
+
+    #[test]
+    fn round_trip_v1_1() {
+        let mut test_file = TestFile::new();
+        let tags = ID3v1 {
+            title: "testsong".into(),
+            artist: "testartist".into(),
+            album: "testalbum".into(),
+            year: "2024".into(),
+            comment: "testcomment".into(),
+            genre: 5,
+            track: Some(7),
+            config: Config::default(),
+        };
+        tags.write(&mut test_file).unwrap();
+
+        assert_eq!(test_file.contents[125], 0);
+        assert_eq!(test_file.contents[126], 7);
+
+        let read_back = ID3v1::read(&mut test_file).unwrap();
+        assert_eq!(read_back.comment.0.trim_end_matches('\0'), "testcomment");
+        assert_eq!(read_back.track, Some(7));
+    }
+
+    #[test]
+    fn artists_split_and_join_on_configured_separator() {
+        let mut tags = ID3v1 {
+            title: "".into(),
+            artist: "".into(),
+            album: "".into(),
+            year: "".into(),
+            comment: "".into(),
+            genre: 0,
+            track: None,
+            config: Config {
+                sep_artist: String::from(","),
+            },
+        };
+
+        tags.set_artists(&["Artist One", "Artist Two"]);
+        assert_eq!(tags.artist(), "Artist One,Artist Two");
+        assert_eq!(tags.artists(), vec!["Artist One", "Artist Two"]);
+    }
+
+    #[test]
+    fn to_latin1_lossy_transliterates_accents() {
+        assert_eq!(to_latin1_lossy("café"), b"cafe");
+        assert_eq!(to_latin1_lossy("Straße"), b"Strasse");
+        assert_eq!(to_latin1_lossy("\u{2018}quoted\u{2019} \u{2014} done"), b"'quoted' - done");
+        assert_eq!(to_latin1_lossy("emoji \u{1F600} dropped"), b"emoji  dropped");
+    }
+
+    #[test]
+    fn write_caps_transliterated_field_to_its_byte_width() {
+        let mut test_file = TestFile::new();
+        let tags = ID3v1 {
+            title: "Stra\u{df}e Stra\u{df}e Stra\u{df}e Stra\u{df}e".into(),
+            artist: "".into(),
+            album: "".into(),
+            year: "".into(),
+            comment: "".into(),
+            genre: 0,
+            track: None,
+            config: Config::default(),
+        };
+        tags.write(&mut test_file).unwrap();
+
+        assert_eq!(test_file.contents.len(), 128);
+        assert_eq!(&test_file.contents[3..33], b"Strasse Strasse Strasse Strass");
+    }
+
+    #[test]
+    fn from_filename_maps_components_by_count() {
+        let config = Config::default();
+
+        let title_only = ID3v1::from_filename("Title.mp3", &config);
+        assert_eq!(title_only.title(), "Title");
+        assert_eq!(title_only.artist(), "");
+
+        let artist_title = ID3v1::from_filename("Artist - Title.mp3", &config);
+        assert_eq!(artist_title.artist(), "Artist");
+        assert_eq!(artist_title.title(), "Title");
+
+        let artist_album_title =
+            ID3v1::from_filename("Artist - Album - Title.mp3", &config);
+        assert_eq!(artist_album_title.artist(), "Artist");
+        assert_eq!(artist_album_title.album(), "Album");
+        assert_eq!(artist_album_title.title(), "Title");
+
+        let full = ID3v1::from_filename("Artist - Album - 7 - Title.mp3", &config);
+        assert_eq!(full.artist(), "Artist");
+        assert_eq!(full.album(), "Album");
+        assert_eq!(full.title(), "Title");
+        assert_eq!(full.track, Some(7));
+    }
+
+    #[test]
+    fn from_filename_keeps_literal_dashes_inside_components() {
+        let config = Config::default();
+
+        let tags = ID3v1::from_filename("Artist - Al-bum - Drum'n'Bass Title.mp3", &config);
+        assert_eq!(tags.artist(), "Artist");
+        assert_eq!(tags.album(), "Al-bum");
+        assert_eq!(tags.title(), "Drum'n'Bass Title");
+    }
+
+    #[test]
+    fn set_genre_round_trips_through_its_numeric_byte() {
+        let mut tags = ID3v1::from_filename("Title.mp3", &Config::default());
+
+        tags.set_genre(17);
+        assert_eq!(tags.genre(), "Rock");
+
+        tags.set_genre(9);
+        assert_eq!(tags.genre(), "Metal");
+    }
+
+    #[test]
+    fn remove_title_clears_the_title_field() {
+        let mut tags = ID3v1::from_filename("Artist - Title.mp3", &Config::default());
+        assert_eq!(tags.title(), "Title");
+
+        tags.remove_title();
+        assert_eq!(tags.title(), "");
+    }
+
+    #[test]
+    fn set_genre_by_name_accepts_known_names_and_rejects_unknown_ones() {
+        let mut tags = ID3v1::from_filename("Title.mp3", &Config::default());
+
+        assert!(tags.set_genre_by_name("Ska"));
+        assert_eq!(tags.genre(), "Ska");
+
+        assert!(!tags.set_genre_by_name("Not a real genre"));
+        // The genre from before the rejected call is left untouched.
+        assert_eq!(tags.genre(), "Ska");
+    }
 }
@@ -1,29 +1,480 @@
-use clap::{Arg, Command};
-use std::{fs::File, path::PathBuf};
+use clap::{value_parser, Arg, Command};
+use std::io::{self, BufRead, Seek, SeekFrom, Write};
+use std::process::ExitCode;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
 
-use tag_changer::{ID3v1, ReadError};
+use tag_changer::{AudioTag, Config, ID3v1, ReadError};
 
-fn main() {
+const EXIT_IO_ERROR: u8 = 1;
+const EXIT_TAG_ERROR: u8 = 2;
+
+fn main() -> ExitCode {
     let matches = Command::new("tag-changer")
         .author("Hugo van Schalm")
         .version("0.1.0")
-        .arg(
-            Arg::new("file")
-                .required(true)
-                .value_parser(clap::builder::StringValueParser::new()),
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("show")
+                .about("Print the tags of a file")
+                .arg(file_arg()),
+        )
+        .subcommand(
+            Command::new("set")
+                .about("Set one or more tag fields of a file")
+                .arg(file_arg())
+                .arg(Arg::new("title").long("title"))
+                .arg(Arg::new("artist").long("artist"))
+                .arg(Arg::new("album").long("album"))
+                .arg(Arg::new("year").long("year"))
+                .arg(Arg::new("comment").long("comment"))
+                .arg(Arg::new("genre").long("genre"))
+                .arg(
+                    Arg::new("track")
+                        .long("track")
+                        .value_parser(value_parser!(u8)),
+                ),
+        )
+        .subcommand(
+            Command::new("clear")
+                .about("Remove the ID3v1 tag from a file")
+                .arg(file_arg()),
         )
         .get_matches();
 
-    let filestring: &String = matches.get_one("file").unwrap();
-    let filepath = PathBuf::from(filestring);
+    match matches.subcommand() {
+        Some(("show", sub)) => show(sub, &mut io::stdin().lock()),
+        Some(("set", sub)) => set(sub, &mut io::stdin().lock()),
+        Some(("clear", sub)) => clear(sub),
+        _ => unreachable!("a subcommand is required"),
+    }
+}
+
+fn file_arg() -> Arg {
+    Arg::new("file")
+        .required(true)
+        .value_parser(clap::builder::StringValueParser::new())
+}
+
+fn open_file(filepath: &PathBuf) -> Result<std::fs::File, ExitCode> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(filepath)
+        .map_err(|err| {
+            eprintln!("Could not open file: {}", err);
+            ExitCode::from(EXIT_IO_ERROR)
+        })
+}
+
+fn open_file_read_only(filepath: &PathBuf) -> Result<std::fs::File, ExitCode> {
+    OpenOptions::new().read(true).open(filepath).map_err(|err| {
+        eprintln!("Could not open file: {}", err);
+        ExitCode::from(EXIT_IO_ERROR)
+    })
+}
+
+fn show(matches: &clap::ArgMatches, input: &mut impl BufRead) -> ExitCode {
+    let filepath = PathBuf::from(matches.get_one::<String>("file").unwrap());
+
+    let mut file = match open_file_read_only(&filepath) {
+        Ok(file) => file,
+        Err(code) => return code,
+    };
+
+    match ID3v1::read(&mut file) {
+        Ok(tags) => {
+            println!("{}", tags);
+            ExitCode::SUCCESS
+        }
+        Err(ReadError::ID3) => {
+            eprintln!("Could not parse tags of file {}", filepath.display());
+
+            // `show` is read-only: filename synthesis is only printed here,
+            // never persisted back to the file.
+            match offer_tags_from_filename(&filepath, input) {
+                Some(tags) => {
+                    println!("{}", tags);
+                    ExitCode::SUCCESS
+                }
+                None => ExitCode::from(EXIT_TAG_ERROR),
+            }
+        }
+        Err(ReadError::IO(io_err)) => {
+            eprintln!("Could not read file: {}", io_err);
+            ExitCode::from(EXIT_IO_ERROR)
+        }
+    }
+}
+
+fn set(matches: &clap::ArgMatches, input: &mut impl BufRead) -> ExitCode {
+    let filepath = PathBuf::from(matches.get_one::<String>("file").unwrap());
 
-    let mut file = File::open(filepath).unwrap();
+    let mut file = match open_file(&filepath) {
+        Ok(file) => file,
+        Err(code) => return code,
+    };
 
-    let tags = match ID3v1::read(&mut file) {
+    let mut tags = match ID3v1::read(&mut file) {
         Ok(tags) => tags,
-        Err(ReadError::ID3) => panic!("Could not parse tags of file {}", filestring),
-        Err(ReadError::IO(err)) => panic!("Could not open file: {}", err),
+        Err(ReadError::ID3) => {
+            eprintln!("Could not parse tags of file {}", filepath.display());
+
+            match offer_tags_from_filename(&filepath, input) {
+                Some(tags) => tags,
+                None => return ExitCode::from(EXIT_TAG_ERROR),
+            }
+        }
+        Err(ReadError::IO(io_err)) => {
+            eprintln!("Could not read file: {}", io_err);
+            return ExitCode::from(EXIT_IO_ERROR);
+        }
+    };
+
+    if let Some(title) = matches.get_one::<String>("title") {
+        tags.set_title(title);
+    }
+    if let Some(artist) = matches.get_one::<String>("artist") {
+        tags.set_artist(artist);
+    }
+    if let Some(album) = matches.get_one::<String>("album") {
+        tags.set_album(album);
+    }
+    if let Some(year) = matches.get_one::<String>("year") {
+        tags.set_year(year);
+    }
+    if let Some(comment) = matches.get_one::<String>("comment") {
+        tags.set_comment(comment);
+    }
+    if let Some(genre) = matches.get_one::<String>("genre") {
+        if let Ok(byte) = genre.parse() {
+            tags.set_genre(byte);
+        } else if !tags.set_genre_by_name(genre) {
+            eprintln!("Unknown genre: {}", genre);
+            return ExitCode::from(EXIT_TAG_ERROR);
+        }
+    }
+    if let Some(&track) = matches.get_one::<u8>("track") {
+        tags.set_track(Some(track));
+    }
+
+    write_tags(tags, &mut file)
+}
+
+fn clear(matches: &clap::ArgMatches) -> ExitCode {
+    let filepath = PathBuf::from(matches.get_one::<String>("file").unwrap());
+
+    let mut file = match open_file(&filepath) {
+        Ok(file) => file,
+        Err(code) => return code,
     };
 
-    println!("{}", tags);
+    let contents = match ID3v1::get_contents_without_tag(&mut file) {
+        Ok(contents) => contents,
+        Err(err) => {
+            eprintln!("Could not read file: {}", err);
+            return ExitCode::from(EXIT_IO_ERROR);
+        }
+    };
+
+    let result = file
+        .seek(SeekFrom::Start(0))
+        .and_then(|_| file.write_all(&contents))
+        .and_then(|()| file.set_len(contents.len() as u64));
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Could not write file: {}", err);
+            ExitCode::from(EXIT_IO_ERROR)
+        }
+    }
+}
+
+fn write_tags(tags: ID3v1, file: &mut std::fs::File) -> ExitCode {
+    match tags.write(file) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Could not write file: {}", err);
+            ExitCode::from(EXIT_IO_ERROR)
+        }
+    }
+}
+
+/// Asks the user whether to synthesize tags from the file's name, and
+/// does so if they agree.
+fn offer_tags_from_filename(filepath: &Path, input: &mut impl BufRead) -> Option<ID3v1> {
+    println!("Synthesize tags from the filename instead? [y/N]");
+
+    let mut answer = String::new();
+    input.read_line(&mut answer).unwrap();
+
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        return None;
+    }
+
+    let filename = filepath.file_name()?.to_string_lossy();
+    Some(ID3v1::from_filename(&filename, &Config::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read as _};
+    use tag_changer::AudioTag;
+
+    fn cli() -> Command {
+        Command::new("tag-changer")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("show")
+                    .about("Print the tags of a file")
+                    .arg(file_arg()),
+            )
+            .subcommand(
+                Command::new("set")
+                    .about("Set one or more tag fields of a file")
+                    .arg(file_arg())
+                    .arg(Arg::new("title").long("title"))
+                    .arg(Arg::new("artist").long("artist"))
+                    .arg(Arg::new("album").long("album"))
+                    .arg(Arg::new("year").long("year"))
+                    .arg(Arg::new("comment").long("comment"))
+                    .arg(Arg::new("genre").long("genre"))
+                    .arg(
+                        Arg::new("track")
+                            .long("track")
+                            .value_parser(value_parser!(u8)),
+                    ),
+            )
+            .subcommand(
+                Command::new("clear")
+                    .about("Remove the ID3v1 tag from a file")
+                    .arg(file_arg()),
+            )
+    }
+
+    /// A file path inside its own directory under the system temp directory,
+    /// with the whole directory removed when dropped.
+    struct TempPath(PathBuf);
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            if let Some(dir) = self.0.parent() {
+                let _ = std::fs::remove_dir_all(dir);
+            }
+        }
+    }
+
+    /// Reserves a fresh temp directory and returns the path `filename` would
+    /// have inside it, so tests can use a meaningful filename (e.g. one
+    /// `ID3v1::from_filename` can parse) without colliding with other tests.
+    fn unique_temp_path(filename: &str) -> TempPath {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("tag-changer-test-{}-{}", std::process::id(), n));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut path = dir;
+        path.push(filename);
+        TempPath(path)
+    }
+
+    fn tagged_temp_file() -> TempPath {
+        let path = unique_temp_path("Artist - Title.mp3");
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path.0)
+            .unwrap();
+        file.write_all(&[0u8; 64]).unwrap();
+
+        let mut tags = ID3v1::from_filename("Artist - Title.mp3", &Config::default());
+        tags.set_year("2001");
+        tags.write(&mut file).unwrap();
+
+        path
+    }
+
+    /// A file big enough for `ID3v1::read` to look for a tag, but without
+    /// a `TAG` trailer, so reads fail with `ReadError::ID3`.
+    fn untagged_temp_file(filename: &str) -> TempPath {
+        let path = unique_temp_path(filename);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path.0)
+            .unwrap();
+        file.write_all(&[0u8; 128]).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn set_applies_field_overrides_on_top_of_the_existing_tag() {
+        let file = tagged_temp_file();
+        let path = file.0.to_str().unwrap().to_string();
+
+        let matches = cli()
+            .try_get_matches_from([
+                "tag-changer",
+                "set",
+                &path,
+                "--title",
+                "Overridden Title",
+                "--track",
+                "7",
+            ])
+            .unwrap();
+        let sub = matches.subcommand_matches("set").unwrap();
+
+        let code = set(sub, &mut Cursor::new(b"" as &[u8]));
+        assert_eq!(code, ExitCode::SUCCESS);
+
+        let mut reopened = std::fs::File::open(&file.0).unwrap();
+        let result = ID3v1::read(&mut reopened).unwrap();
+        assert_eq!(result.title(), "Overridden Title");
+        assert_eq!(result.track(), Some(7));
+        // Fields that were not overridden keep their previously read values.
+        assert_eq!(result.artist(), "Artist");
+        assert_eq!(result.year(), "2001");
+    }
+
+    #[test]
+    fn clear_truncates_the_trailing_tag() {
+        let file = tagged_temp_file();
+        let path = file.0.to_str().unwrap().to_string();
+
+        let matches = cli()
+            .try_get_matches_from(["tag-changer", "clear", &path])
+            .unwrap();
+        let sub = matches.subcommand_matches("clear").unwrap();
+
+        let code = clear(sub);
+        assert_eq!(code, ExitCode::SUCCESS);
+
+        let mut reopened = std::fs::File::open(&file.0).unwrap();
+        let mut contents = Vec::new();
+        reopened.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, vec![0u8; 64]);
+        assert!(matches!(
+            ID3v1::read(&mut reopened),
+            Err(ReadError::ID3) | Err(ReadError::IO(_))
+        ));
+    }
+
+    #[test]
+    fn show_does_not_modify_the_file() {
+        let file = tagged_temp_file();
+        let path = file.0.to_str().unwrap().to_string();
+
+        let before = std::fs::read(&file.0).unwrap();
+
+        let matches = cli()
+            .try_get_matches_from(["tag-changer", "show", &path])
+            .unwrap();
+        let sub = matches.subcommand_matches("show").unwrap();
+
+        let code = show(sub, &mut Cursor::new(b"" as &[u8]));
+        assert_eq!(code, ExitCode::SUCCESS);
+
+        let after = std::fs::read(&file.0).unwrap();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn set_writes_filename_synthesized_tags_with_overrides_when_accepted() {
+        let file = untagged_temp_file("Artist - Title.mp3");
+        let path = file.0.to_str().unwrap().to_string();
+
+        let matches = cli()
+            .try_get_matches_from(["tag-changer", "set", &path, "--title", "Overridden"])
+            .unwrap();
+        let sub = matches.subcommand_matches("set").unwrap();
+
+        let code = set(sub, &mut Cursor::new(b"y\n" as &[u8]));
+        assert_eq!(code, ExitCode::SUCCESS);
+
+        let mut reopened = std::fs::File::open(&file.0).unwrap();
+        let result = ID3v1::read(&mut reopened).unwrap();
+        assert_eq!(result.title(), "Overridden");
+        assert_eq!(result.artist(), "Artist");
+    }
+
+    #[test]
+    fn set_leaves_the_file_untouched_when_synthesis_is_declined() {
+        let file = untagged_temp_file("Artist - Title.mp3");
+        let path = file.0.to_str().unwrap().to_string();
+        let before = std::fs::read(&file.0).unwrap();
+
+        let matches = cli()
+            .try_get_matches_from(["tag-changer", "set", &path, "--title", "Overridden"])
+            .unwrap();
+        let sub = matches.subcommand_matches("set").unwrap();
+
+        let code = set(sub, &mut Cursor::new(b"n\n" as &[u8]));
+        assert_eq!(code, ExitCode::from(EXIT_TAG_ERROR));
+
+        let after = std::fs::read(&file.0).unwrap();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn show_prints_filename_synthesized_tags_without_writing_when_accepted() {
+        let file = untagged_temp_file("Artist - Title.mp3");
+        let path = file.0.to_str().unwrap().to_string();
+        let before = std::fs::read(&file.0).unwrap();
+
+        let matches = cli()
+            .try_get_matches_from(["tag-changer", "show", &path])
+            .unwrap();
+        let sub = matches.subcommand_matches("show").unwrap();
+
+        let code = show(sub, &mut Cursor::new(b"y\n" as &[u8]));
+        assert_eq!(code, ExitCode::SUCCESS);
+
+        let after = std::fs::read(&file.0).unwrap();
+        assert_eq!(before, after, "show must never persist synthesized tags");
+    }
+
+    #[test]
+    fn show_exits_with_tag_error_when_synthesis_is_declined() {
+        let file = untagged_temp_file("Artist - Title.mp3");
+        let path = file.0.to_str().unwrap().to_string();
+
+        let matches = cli()
+            .try_get_matches_from(["tag-changer", "show", &path])
+            .unwrap();
+        let sub = matches.subcommand_matches("show").unwrap();
+
+        let code = show(sub, &mut Cursor::new(b"n\n" as &[u8]));
+        assert_eq!(code, ExitCode::from(EXIT_TAG_ERROR));
+    }
+
+    #[test]
+    fn set_rejects_an_unknown_genre_name() {
+        let file = tagged_temp_file();
+        let path = file.0.to_str().unwrap().to_string();
+        let before = std::fs::read(&file.0).unwrap();
+
+        let matches = cli()
+            .try_get_matches_from(["tag-changer", "set", &path, "--genre", "Not a real genre"])
+            .unwrap();
+        let sub = matches.subcommand_matches("set").unwrap();
+
+        let code = set(sub, &mut Cursor::new(b"" as &[u8]));
+        assert_eq!(code, ExitCode::from(EXIT_TAG_ERROR));
+
+        let after = std::fs::read(&file.0).unwrap();
+        assert_eq!(before, after, "an unknown genre must not be written");
+    }
 }